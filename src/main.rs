@@ -1,66 +1,460 @@
 extern crate rand;
+extern crate thiserror;
 
 use rand::Rng;
+use rand::RngCore;
 use std::iter::Peekable;
+use std::str::Chars;
 use std::fmt;
+use thiserror::Error;
 
-struct DiceRoll { count: u32, sides: u32 }
+#[derive(Copy, Clone)]
+enum Keep { High(u32), Low(u32) }
+
+struct DiceRoll { count: u32, sides: u32, keep: Option<Keep>, explode: bool }
+struct DicePool { count: u32, target: u32, again: u32 }
 struct Scalar { value: i32 }
 struct Add { lhs: Box<Expression>, rhs: Box<Expression> }
 struct Subtract { lhs: Box<Expression>, rhs: Box<Expression> }
+struct Multiply { lhs: Box<Expression>, rhs: Box<Expression> }
+struct Divide { lhs: Box<Expression>, rhs: Box<Expression> }
+struct Negate { operand: Box<Expression> }
+
+#[derive(Debug, Error)]
+pub enum ParseError {
+    #[error("unexpected character '{found}' at position {pos}")]
+    UnexpectedChar { found: char, pos: usize },
+    #[error("missing operand at position {pos}")]
+    MissingOperand { pos: usize },
+    #[error("unknown operator '{op}' at position {pos}")]
+    UnknownOperator { op: char, pos: usize },
+    #[error("division by zero at position {pos}")]
+    DivisionByZero { pos: usize },
+    #[error("a dice pool cannot be combined with '+', '-', '*' or '/' (position {pos})")]
+    PoolArithmetic { pos: usize },
+    #[error("unexpected end of input")]
+    UnexpectedEnd,
+}
+
+impl ParseError {
+    // Character offset the error points at, if the error is tied to a
+    // specific position in the input.
+    fn pos(&self) -> Option<usize> {
+        match *self {
+            ParseError::UnexpectedChar { pos, .. } => Some(pos),
+            ParseError::MissingOperand { pos } => Some(pos),
+            ParseError::UnknownOperator { pos, .. } => Some(pos),
+            ParseError::DivisionByZero { pos } => Some(pos),
+            ParseError::PoolArithmetic { pos } => Some(pos),
+            ParseError::UnexpectedEnd => None,
+        }
+    }
+
+    // Render the error beneath a copy of the original input with a caret
+    // pointing at the offending character.
+    pub fn render(&self, input: &str) -> String {
+        let column = self.pos().unwrap_or(input.chars().count());
+        return format!("{}\n{}^\n{}", input, " ".repeat(column), self);
+    }
+}
+
+// A character iterator that tracks the running offset so parse errors can
+// record where they occurred.
+struct Scanner<'a> {
+    chars: Peekable<Chars<'a>>,
+    pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn new(input: &'a str) -> Scanner<'a> {
+        return Scanner { chars: input.chars().peekable(), pos: 0 };
+    }
+
+    fn peek(&mut self) -> Option<&char> {
+        return self.chars.peek();
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next();
+        if c.is_some() {
+            self.pos += 1;
+        }
+        return c;
+    }
+
+    fn pos(&self) -> usize {
+        return self.pos;
+    }
+}
 
-pub struct ParseError {
-    message: &'static str,
+// The itemized outcome of evaluating an expression: the final `total`
+// alongside a tree mirroring the expression so the individual die faces
+// and per-subexpression contributions can be shown to the user.
+pub struct RollResult {
+    pub total: i32,
+    expr: String,
+    prec: u8,
+    detail: Detail,
+}
+
+enum Detail {
+    // Every individual die face a `DiceRoll`/`DicePool` produced.
+    Faces(Vec<u32>),
+    // A literal scalar operand.
+    Value(i32),
+    // A binary operator with its two evaluated operands.
+    Binary(&'static str, Box<RollResult>, Box<RollResult>),
+    // A unary operator (currently only negation) with its operand.
+    Unary(&'static str, Box<RollResult>),
 }
 
 pub trait Expression : fmt::Display {
-    fn get_value(&self) -> i32;
+    // Core evaluation: all randomness is drawn from the injected `rng`, so
+    // a seeded generator produces reproducible results.
+    fn evaluate_with(&self, rng: &mut dyn RngCore) -> RollResult;
+    // Convenience wrappers: `evaluate` and `get_value` roll against the
+    // thread-local RNG, `get_value_with` against the supplied `rng`.
+    fn evaluate(&self) -> RollResult { self.evaluate_with(&mut rand::thread_rng()) }
+    fn get_value(&self) -> i32 { self.evaluate().total }
+    fn get_value_with(&self, rng: &mut dyn RngCore) -> i32 { self.evaluate_with(rng).total }
+    // Binding strength used by `Display` to decide when an operand needs
+    // to be wrapped in parentheses: additive is loosest, then
+    // multiplicative, with atoms and unary minus binding tightest.
+    fn precedence(&self) -> u8;
+    // Fold a randomness-free subexpression to its constant value, or return
+    // `None` when any die roll is involved. Division uses this to reject a
+    // statically zero divisor (e.g. `3 - 3`) while parsing instead of
+    // panicking when the value is evaluated.
+    fn fold_const(&self) -> Option<i32> { None }
+    // True for success-pool rolls, which count successes rather than sum
+    // faces and so can't be mixed into arithmetic with other terms.
+    fn is_pool(&self) -> bool { false }
+}
+
+// Render `child` as an operand of an expression whose precedence is
+// `parent`. A left operand only needs parentheses when it binds strictly
+// looser than its parent; a right operand also needs them at equal
+// precedence so that `a - (b - c)` and `2 * (d6 + 3)` keep their grouping.
+fn fmt_operand(f: &mut fmt::Formatter, parent: u8, child: &Box<Expression>, right: bool) -> fmt::Result {
+    let wrap = if right { child.precedence() <= parent } else { child.precedence() < parent };
+    if wrap {
+        write!(f, "({})", child)
+    } else {
+        write!(f, "{}", child)
+    }
+}
+
+impl RollResult {
+    // Render just the itemized side of the breakdown, e.g. `[4, 1, 6] + 2`,
+    // reusing the same precedence rules the expression `Display` uses.
+    fn fmt_items(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.detail {
+            Detail::Faces(ref faces) => {
+                write!(f, "[")?;
+                for (i, face) in faces.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", face)?;
+                }
+                write!(f, "]")
+            },
+            Detail::Value(value) => write!(f, "{}", value),
+            Detail::Binary(op, ref lhs, ref rhs) => {
+                lhs.fmt_items_operand(f, self.prec, false)?;
+                write!(f, " {} ", op)?;
+                rhs.fmt_items_operand(f, self.prec, true)
+            },
+            Detail::Unary(op, ref operand) => {
+                write!(f, "{}", op)?;
+                operand.fmt_items_operand(f, self.prec, false)
+            },
+        }
+    }
+
+    fn fmt_items_operand(&self, f: &mut fmt::Formatter, parent: u8, right: bool) -> fmt::Result {
+        let wrap = if right { self.prec <= parent } else { self.prec < parent };
+        if wrap {
+            write!(f, "(")?;
+            self.fmt_items(f)?;
+            write!(f, ")")
+        } else {
+            self.fmt_items(f)
+        }
+    }
+}
+
+impl fmt::Display for RollResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} => ", self.expr)?;
+        self.fmt_items(f)?;
+        write!(f, " = {}", self.total)
+    }
 }
 
 impl fmt::Display for Add {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{} + {}", self.lhs, self.rhs)
+        fmt_operand(f, 1, &self.lhs, false)?;
+        write!(f, " + ")?;
+        fmt_operand(f, 1, &self.rhs, true)
     }
 }
 
 impl Expression for Add {
-    fn get_value(&self) -> i32 {
-        self.lhs.get_value() + self.rhs.get_value()
+    fn evaluate_with(&self, rng: &mut dyn RngCore) -> RollResult {
+        let lhs = self.lhs.evaluate_with(rng);
+        let rhs = self.rhs.evaluate_with(rng);
+        RollResult {
+            total: lhs.total + rhs.total,
+            expr: self.to_string(),
+            prec: 1,
+            detail: Detail::Binary("+", Box::new(lhs), Box::new(rhs)),
+        }
+    }
+
+    fn precedence(&self) -> u8 { 1 }
+
+    fn fold_const(&self) -> Option<i32> {
+        Some(self.lhs.fold_const()? + self.rhs.fold_const()?)
     }
 }
 
 impl fmt::Display for Subtract {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{} - {}", self.lhs, self.rhs)
+        fmt_operand(f, 1, &self.lhs, false)?;
+        write!(f, " - ")?;
+        fmt_operand(f, 1, &self.rhs, true)
     }
 }
 
 impl Expression for Subtract {
-    fn get_value(&self) -> i32 {
-        self.lhs.get_value() - self.rhs.get_value()
+    fn evaluate_with(&self, rng: &mut dyn RngCore) -> RollResult {
+        let lhs = self.lhs.evaluate_with(rng);
+        let rhs = self.rhs.evaluate_with(rng);
+        RollResult {
+            total: lhs.total - rhs.total,
+            expr: self.to_string(),
+            prec: 1,
+            detail: Detail::Binary("-", Box::new(lhs), Box::new(rhs)),
+        }
     }
+
+    fn precedence(&self) -> u8 { 1 }
+
+    fn fold_const(&self) -> Option<i32> {
+        Some(self.lhs.fold_const()? - self.rhs.fold_const()?)
+    }
+}
+
+impl fmt::Display for Multiply {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_operand(f, 2, &self.lhs, false)?;
+        write!(f, " * ")?;
+        fmt_operand(f, 2, &self.rhs, true)
+    }
+}
+
+impl Expression for Multiply {
+    fn evaluate_with(&self, rng: &mut dyn RngCore) -> RollResult {
+        let lhs = self.lhs.evaluate_with(rng);
+        let rhs = self.rhs.evaluate_with(rng);
+        RollResult {
+            total: lhs.total * rhs.total,
+            expr: self.to_string(),
+            prec: 2,
+            detail: Detail::Binary("*", Box::new(lhs), Box::new(rhs)),
+        }
+    }
+
+    fn precedence(&self) -> u8 { 2 }
+
+    fn fold_const(&self) -> Option<i32> {
+        Some(self.lhs.fold_const()? * self.rhs.fold_const()?)
+    }
+
+    fn is_pool(&self) -> bool { self.lhs.is_pool() || self.rhs.is_pool() }
+}
+
+impl fmt::Display for Divide {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt_operand(f, 2, &self.lhs, false)?;
+        write!(f, " / ")?;
+        fmt_operand(f, 2, &self.rhs, true)
+    }
+}
+
+impl Expression for Divide {
+    fn evaluate_with(&self, rng: &mut dyn RngCore) -> RollResult {
+        let lhs = self.lhs.evaluate_with(rng);
+        let rhs = self.rhs.evaluate_with(rng);
+        // A statically zero divisor is rejected while parsing, but a divisor
+        // that only reaches zero at roll time (e.g. `d6 - d6`) can still slip
+        // through, so guard with `checked_div` rather than panicking. i32
+        // division already truncates toward zero.
+        RollResult {
+            total: lhs.total.checked_div(rhs.total).unwrap_or(0),
+            expr: self.to_string(),
+            prec: 2,
+            detail: Detail::Binary("/", Box::new(lhs), Box::new(rhs)),
+        }
+    }
+
+    fn precedence(&self) -> u8 { 2 }
+
+    fn fold_const(&self) -> Option<i32> {
+        let lhs = self.lhs.fold_const()?;
+        let rhs = self.rhs.fold_const()?;
+        lhs.checked_div(rhs)
+    }
+
+    fn is_pool(&self) -> bool { self.lhs.is_pool() || self.rhs.is_pool() }
+}
+
+impl fmt::Display for Negate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "-")?;
+        fmt_operand(f, 3, &self.operand, false)
+    }
+}
+
+impl Expression for Negate {
+    fn evaluate_with(&self, rng: &mut dyn RngCore) -> RollResult {
+        let operand = self.operand.evaluate_with(rng);
+        RollResult {
+            total: -operand.total,
+            expr: self.to_string(),
+            prec: 3,
+            detail: Detail::Unary("-", Box::new(operand)),
+        }
+    }
+
+    fn precedence(&self) -> u8 { 3 }
+
+    fn fold_const(&self) -> Option<i32> {
+        Some(-self.operand.fold_const()?)
+    }
+
+    fn is_pool(&self) -> bool { self.operand.is_pool() }
 }
 
 impl fmt::Display for DiceRoll {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self.count {
-            1 => write!(f, "d{}", self.sides),
-            _ => write!(f, "{}d{}", self.count, self.sides),
+            1 => write!(f, "d{}", self.sides)?,
+            _ => write!(f, "{}d{}", self.count, self.sides)?,
+        }
+        match self.keep {
+            Some(Keep::High(n)) => write!(f, "kh{}", n)?,
+            Some(Keep::Low(n)) => write!(f, "kl{}", n)?,
+            None => {},
+        }
+        if self.explode {
+            write!(f, "!")?;
         }
+
+        return Ok(());
     }
 }
 
 impl Expression for DiceRoll {
-    fn get_value(&self) -> i32 {
-        let mut sum = 0;
+    fn evaluate_with(&self, rng: &mut dyn RngCore) -> RollResult {
+        let mut rolls: Vec<u32> = Vec::with_capacity(self.count as usize);
+        let mut faces: Vec<u32> = Vec::with_capacity(self.count as usize);
         for _ in 0..self.count {
-            sum += roll_die(self.sides);
+            let mut subtotal = roll_die(self.sides, rng);
+            faces.push(subtotal);
+            if self.explode {
+                // Reroll while a die lands on its maximum face, capping the
+                // chain so `sides == 1` can't spin forever.
+                let mut die = subtotal;
+                let mut iterations = 0;
+                while die == self.sides && iterations < 100 {
+                    die = roll_die(self.sides, rng);
+                    faces.push(die);
+                    subtotal += die;
+                    iterations += 1;
+                }
+            }
+            rolls.push(subtotal);
         }
 
-        return sum as i32;
+        let total = match self.keep {
+            Some(Keep::High(n)) => {
+                let mut kept = rolls.clone();
+                kept.sort();
+                kept.reverse();
+                let take = (n as usize).min(kept.len());
+                kept[..take].iter().sum::<u32>() as i32
+            },
+            Some(Keep::Low(n)) => {
+                let mut kept = rolls.clone();
+                kept.sort();
+                let take = (n as usize).min(kept.len());
+                kept[..take].iter().sum::<u32>() as i32
+            },
+            None => rolls.iter().sum::<u32>() as i32,
+        };
+
+        return RollResult {
+            total: total,
+            expr: self.to_string(),
+            prec: 3,
+            detail: Detail::Faces(faces),
+        };
+    }
+
+    fn precedence(&self) -> u8 { 3 }
+}
+
+impl fmt::Display for DicePool {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}p", self.count)?;
+        if self.target != 8 {
+            write!(f, " t{}", self.target)?;
+        }
+        if self.again != 10 {
+            write!(f, " a{}", self.again)?;
+        }
+
+        return Ok(());
     }
 }
 
+impl Expression for DicePool {
+    fn evaluate_with(&self, rng: &mut dyn RngCore) -> RollResult {
+        let mut faces: Vec<u32> = Vec::with_capacity(self.count as usize);
+        let mut successes = 0;
+        let mut remaining = self.count;
+        let mut extra = 0;
+        while remaining > 0 {
+            remaining -= 1;
+            let face = roll_die(10, rng);
+            faces.push(face);
+            if face >= self.target {
+                successes += 1;
+            }
+            // X-again: a die reaching the again-value spawns another die
+            // that is rolled and counted too. Cap the chain so a
+            // pathologically low again-value can't loop forever.
+            if face >= self.again && extra < 1000 {
+                extra += 1;
+                remaining += 1;
+            }
+        }
+
+        return RollResult {
+            total: successes,
+            expr: self.to_string(),
+            prec: 3,
+            detail: Detail::Faces(faces),
+        };
+    }
+
+    fn precedence(&self) -> u8 { 3 }
+
+    fn is_pool(&self) -> bool { true }
+}
+
 impl fmt::Display for Scalar {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         self.value.fmt(f)
@@ -68,34 +462,41 @@ impl fmt::Display for Scalar {
 }
 
 impl Expression for Scalar {
-    fn get_value(&self) -> i32 {
-        return self.value;
+    fn evaluate_with(&self, _rng: &mut dyn RngCore) -> RollResult {
+        return RollResult {
+            total: self.value,
+            expr: self.to_string(),
+            prec: 3,
+            detail: Detail::Value(self.value),
+        };
     }
+
+    fn precedence(&self) -> u8 { 3 }
+
+    fn fold_const(&self) -> Option<i32> { Some(self.value) }
 }
 
-fn roll_die(sides: u32) -> u32
+fn roll_die(sides: u32, rng: &mut dyn RngCore) -> u32
 {
-    return (rand::thread_rng().gen::<u32>() % sides) + 1;
+    return rng.gen_range(1..=sides);
 }
 
-fn read_u32<T>(roll_def: &mut Peekable<T>, default: u32) -> u32
-    where T: Iterator<Item=char> {
+fn read_u32(scanner: &mut Scanner, default: u32) -> u32 {
     let mut nums_found = false;
     let mut result = 0;
-    while match roll_def.peek() {
+    while match scanner.peek() {
         Some(&'0'...'9') => true,
         _ => false
     } {
         nums_found = true;
         result *= 10;
-        result += (roll_def.next().unwrap() as u32) - ('0' as u32);
+        result += (scanner.next().unwrap() as u32) - ('0' as u32);
     }
 
     return if nums_found { result } else { default };
 }
 
-fn read_operand<T>(chars: &mut Peekable<T>) -> Option<Box<Expression>>
-    where T: Iterator<Item=char> {
+fn read_operand(chars: &mut Scanner) -> Option<Box<Expression>> {
     match chars.peek() {
         Some(&'0'...'9') | Some(&'d') => {},
         _ => return None,
@@ -106,7 +507,47 @@ fn read_operand<T>(chars: &mut Peekable<T>) -> Option<Box<Expression>>
         Some(&'d') => {
             chars.next();
             let sides_count = read_u32(chars, 6);
-            return Some(Box::new(DiceRoll { count: die_count, sides: sides_count }));
+            let mut keep = None;
+            let mut explode = false;
+            loop {
+                match chars.peek() {
+                    Some(&'k') => {
+                        chars.next();
+                        match chars.next() {
+                            Some('h') => keep = Some(Keep::High(read_u32(chars, 1))),
+                            Some('l') => keep = Some(Keep::Low(read_u32(chars, 1))),
+                            _ => return None,
+                        }
+                    },
+                    Some(&'!') => { chars.next(); explode = true; },
+                    _ => break,
+                }
+            }
+            return Some(Box::new(DiceRoll { count: die_count, sides: sides_count, keep: keep, explode: explode }));
+        },
+        Some(&'p') => {
+            chars.next();
+            // Accept the long spelling `pool` as well as the short `p`.
+            if chars.peek() == Some(&'o') {
+                for expected in ['o', 'o', 'l'].iter() {
+                    if chars.peek() == Some(expected) {
+                        chars.next();
+                    } else {
+                        return None;
+                    }
+                }
+            }
+            let mut target = 8;
+            let mut again = 10;
+            loop {
+                chomp(chars);
+                match chars.peek() {
+                    Some(&'t') => { chars.next(); target = read_u32(chars, 8); },
+                    Some(&'a') => { chars.next(); again = read_u32(chars, 10); },
+                    _ => break,
+                }
+            }
+            return Some(Box::new(DicePool { count: die_count, target: target, again: again }));
         },
         _ => {},
     }
@@ -114,47 +555,132 @@ fn read_operand<T>(chars: &mut Peekable<T>) -> Option<Box<Expression>>
     return Some(Box::new(Scalar{ value: die_count as i32 }));
 }
 
-fn chomp<T>(chars: &mut Peekable<T>) where T: Iterator<Item=char>
+fn chomp(chars: &mut Scanner)
 {
     while chars.peek().unwrap_or(&'_').is_whitespace() {
         chars.next();
     }
 }
 
-pub fn roll(roll_def: &str) -> Result<Box<Expression>, ParseError>
-{
-    let mut chars = roll_def.chars().peekable();
-    chomp(&mut chars);
-    let mut result = match read_operand(&mut chars) {
-        Some(x) => x,
-        None => return Err(ParseError{ message: "Invalid roll definition"}),
-    };
-    while chars.peek().is_some() {
-        chomp(&mut chars);
-        match chars.next() {
-            Some(operator) => {
-                chomp(&mut chars);
-                if let Some(rhs) = read_operand(&mut chars) {
-                    result = match operator {
-                        '+' => Box::new(Add { lhs: result, rhs: rhs }),
-                        '-' => Box::new(Subtract { lhs: result, rhs: rhs }),
-                        _ => {
-                            return Err(ParseError{ message: "Invalid roll definition"});
-                        }
-                    };
-                } else {
-                    return Err(ParseError { message: "Missing operand" })
+// factor := '(' expr ')' | '-' factor | operand
+fn parse_factor(chars: &mut Scanner) -> Result<Box<Expression>, ParseError> {
+    chomp(chars);
+    match chars.peek() {
+        Some(&'(') => {
+            chars.next();
+            let inner = parse_expr(chars)?;
+            chomp(chars);
+            match chars.next() {
+                Some(')') => return Ok(inner),
+                Some(found) => return Err(ParseError::UnexpectedChar { found: found, pos: chars.pos() - 1 }),
+                None => return Err(ParseError::UnexpectedEnd),
+            }
+        },
+        Some(&'-') => {
+            chars.next();
+            let operand = parse_factor(chars)?;
+            return Ok(Box::new(Negate{ operand: operand }));
+        },
+        _ => {
+            if chars.peek().is_none() {
+                return Err(ParseError::UnexpectedEnd);
+            }
+            let pos = chars.pos();
+            match read_operand(chars) {
+                Some(x) => return Ok(x),
+                None => return Err(ParseError::MissingOperand { pos: pos }),
+            }
+        },
+    }
+}
+
+// term := factor (('*' | '/') factor)*
+fn parse_term(chars: &mut Scanner) -> Result<Box<Expression>, ParseError> {
+    let mut result = parse_factor(chars)?;
+    loop {
+        chomp(chars);
+        match chars.peek() {
+            Some(&'*') => {
+                let pos = chars.pos();
+                chars.next();
+                let rhs = parse_factor(chars)?;
+                if result.is_pool() || rhs.is_pool() {
+                    return Err(ParseError::PoolArithmetic { pos: pos });
+                }
+                result = Box::new(Multiply { lhs: result, rhs: rhs });
+            },
+            Some(&'/') => {
+                let pos = chars.pos();
+                chars.next();
+                let rhs = parse_factor(chars)?;
+                if result.is_pool() || rhs.is_pool() {
+                    return Err(ParseError::PoolArithmetic { pos: pos });
+                }
+                // Reject a divisor that folds to a static zero (literal `0`
+                // or an all-constant subexpression like `3 - 3`).
+                if rhs.fold_const() == Some(0) {
+                    return Err(ParseError::DivisionByZero { pos: pos });
+                }
+                result = Box::new(Divide { lhs: result, rhs: rhs });
+            },
+            _ => return Ok(result),
+        }
+    }
+}
+
+// expr := term (('+' | '-') term)*
+fn parse_expr(chars: &mut Scanner) -> Result<Box<Expression>, ParseError> {
+    let mut result = parse_term(chars)?;
+    loop {
+        chomp(chars);
+        match chars.peek() {
+            Some(&'+') => {
+                let pos = chars.pos();
+                chars.next();
+                let rhs = parse_term(chars)?;
+                if result.is_pool() || rhs.is_pool() {
+                    return Err(ParseError::PoolArithmetic { pos: pos });
                 }
+                result = Box::new(Add { lhs: result, rhs: rhs });
             },
-            None => {
-                return Ok(result);
+            Some(&'-') => {
+                let pos = chars.pos();
+                chars.next();
+                let rhs = parse_term(chars)?;
+                if result.is_pool() || rhs.is_pool() {
+                    return Err(ParseError::PoolArithmetic { pos: pos });
+                }
+                result = Box::new(Subtract { lhs: result, rhs: rhs });
             },
+            _ => return Ok(result),
         }
-    };
+    }
+}
+
+pub fn roll(roll_def: &str) -> Result<Box<Expression>, ParseError>
+{
+    let mut scanner = Scanner::new(roll_def);
+    let result = parse_expr(&mut scanner)?;
+    chomp(&mut scanner);
+    match scanner.peek() {
+        // Anything left over sits where an operator or the end of input was
+        // expected, so surface it as an unknown operator.
+        Some(&op) => return Err(ParseError::UnknownOperator { op: op, pos: scanner.pos() }),
+        None => {},
+    }
 
     return Ok(result);
 }
 
+// Parse `roll_def` and evaluate it against the caller-supplied RNG. Pass a
+// seeded generator (e.g. `StdRng::seed_from_u64(seed)`) for reproducible
+// results.
+pub fn roll_with_rng(roll_def: &str, rng: &mut dyn RngCore) -> Result<i32, ParseError>
+{
+    let expr = roll(roll_def)?;
+    return Ok(expr.get_value_with(rng));
+}
+
 fn main()
 {
     let request: String;
@@ -166,7 +692,7 @@ fn main()
     match roll(&request) {
         Ok(result) => println!("{}", result.get_value()),
         Err(err) => {
-            println!("ERROR: {}", err.message);
+            println!("ERROR:\n{}", err.render(&request));
             std::process::exit(-1);
         }
     }
@@ -175,6 +701,30 @@ fn main()
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn seeded_rolls_are_reproducible() {
+        // A fixed seed must always yield the same total, and re-seeding with
+        // the same value must reproduce it exactly.
+        let first = roll_with_rng("3d6 + 2", &mut StdRng::seed_from_u64(42)).unwrap();
+        let second = roll_with_rng("3d6 + 2", &mut StdRng::seed_from_u64(42)).unwrap();
+        assert_eq!(first, second);
+
+        let mut rng = StdRng::seed_from_u64(7);
+        let total = roll("4d6kh3").unwrap().get_value_with(&mut rng);
+        // keep-highest-3 of four d6 can never exceed 18 or fall below 3.
+        assert!(total >= 3 && total <= 18);
+    }
+
+    #[test]
+    fn roll_result_renders_itemized_breakdown() {
+        // The itemized `Display` must show each die face and the operands
+        // around it; with a fixed seed the rendering is exact.
+        let result = roll("3d6 + 2").unwrap().evaluate_with(&mut StdRng::seed_from_u64(42));
+        assert_eq!(result.to_string(), "3d6 + 2 => [4, 2, 4] + 2 = 12");
+    }
 
     #[test]
     fn test() {
@@ -187,6 +737,21 @@ mod tests {
             ("d12 + 52", Some("d12 + 52")),
             ("d12 - 8", Some("d12 - 8")),
             ("3d12 - 8 + 10d8", Some("3d12 - 8 + 10d8")),
+            ("2 * (d6 + 3)", Some("2 * (d6 + 3)")),
+            ("d6 + 2 * 3", Some("d6 + 2 * 3")),
+            ("(d6 + 2) * 3", Some("(d6 + 2) * 3")),
+            ("10 / 2 - 1", Some("10 / 2 - 1")),
+            ("-d6 + 4", Some("-d6 + 4")),
+            ("d6 / 0", None),
+            ("4d6kh3", Some("4d6kh3")),
+            ("4d6kl1", Some("4d6kl1")),
+            ("d6!", Some("d6!")),
+            ("4d6kh3!", Some("4d6kh3!")),
+            ("8p", Some("8p")),
+            ("8pool", Some("8p")),
+            ("8p t7 a9", Some("8p t7 a9")),
+            ("8p + 3", None),
+            ("d6 + 8p", None),
         ].into_iter() {
             let result = roll(input);
             println!("testing: {}", input);